@@ -1,3 +1,4 @@
+use crate::command::Command;
 use crate::style::Attribute;
 use std::ops::{BitAnd, BitOr, BitXor};
 
@@ -148,9 +149,60 @@ impl Attributes {
     }
 }
 
+/// A command that applies every [`Attribute`] set in an [`Attributes`] bitset at
+/// once, coalescing them into a single SGR escape sequence instead of emitting one
+/// sequence per attribute.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetAttributes(pub Attributes);
+
+impl Command for SetAttributes {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        let mut ansi_code = String::new();
+        for attribute in self.0 {
+            if !ansi_code.is_empty() {
+                ansi_code.push(';');
+            }
+            ansi_code.push_str(attribute.sgr());
+        }
+
+        if ansi_code.is_empty() {
+            ansi_code
+        } else {
+            format!("\x1B[{}m", ansi_code)
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self, _writer: impl FnMut() -> crate::Result<()>) -> crate::Result<()> {
+        use crate::style::wincon::{set_console_attributes, ConsoleAttributeState};
+
+        if self.0.is_empty() {
+            return Ok(());
+        }
+
+        let mut state = ConsoleAttributeState::new();
+        for attribute in self.0 {
+            if let Ok(code) = attribute.sgr().parse() {
+                state.apply_sgr_code(code);
+            }
+        }
+        set_console_attributes(state.attribute_word());
+        Ok(())
+    }
+}
+
+crate::impl_display!(for SetAttributes);
+
 #[cfg(test)]
 mod tests {
-    use super::{Attribute, Attributes};
+    use super::{Attribute, Attributes, SetAttributes};
+    use crate::command::Command;
 
     #[test]
     fn test_attributes() {
@@ -163,4 +215,16 @@ mod tests {
         attributes.toggle(Attribute::Bold);
         assert!(attributes.is_empty());
     }
+
+    #[test]
+    fn test_set_attributes_empty() {
+        let attributes = SetAttributes(Attributes::default());
+        assert_eq!(attributes.ansi_code(), "");
+    }
+
+    #[test]
+    fn test_set_attributes_coalesces_into_one_sequence() {
+        let attributes = SetAttributes(Attribute::Bold.into());
+        assert_eq!(attributes.ansi_code(), format!("\x1B[{}m", Attribute::Bold.sgr()));
+    }
 }
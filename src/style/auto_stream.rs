@@ -0,0 +1,225 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+
+use super::strip::StripWriter;
+#[cfg(windows)]
+use super::wincon::WinconWriter;
+
+/// Controls how an [`AutoStream`] decides between emitting ANSI escape sequences and
+/// stripping them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Detect whether the wrapped writer is a terminal, honoring the `NO_COLOR`,
+    /// `CLICOLOR_FORCE` and `TERM=dumb` environment conventions.
+    Auto,
+    /// Always force color, using ANSI or the winapi replay path, whichever renders
+    /// correctly on the current console.
+    Always,
+    /// Always emit raw ANSI escape sequences, with no winapi fallback.
+    AlwaysAnsi,
+    /// Never emit ANSI escape sequences; always strip them.
+    Never,
+}
+
+/// The strategy an [`AutoStream`] settled on for its wrapped writer.
+#[derive(Debug)]
+enum Repr<W: Write> {
+    /// Bytes are forwarded to the inner writer unchanged.
+    PassThrough(W),
+    /// Bytes are stripped of ANSI escape sequences before being forwarded.
+    Strip(StripWriter<W>),
+    /// Bytes are parsed as ANSI and replayed through legacy Windows console calls.
+    #[cfg(windows)]
+    WinApi(WinconWriter<W>),
+}
+
+/// A writer adapter that picks, once at construction time, whether to pass ANSI
+/// escape sequences through, strip them, or replay them through legacy Windows
+/// console calls.
+#[derive(Debug)]
+pub struct AutoStream<W: Write> {
+    repr: Repr<W>,
+}
+
+impl<W: Write + IsTerminal> AutoStream<W> {
+    /// Wraps `inner`, resolving `choice` against the wrapped writer right away.
+    pub fn new(inner: W, choice: ColorChoice) -> Self {
+        let is_terminal = inner.is_terminal();
+        Self::with_terminal_hint(inner, choice, is_terminal)
+    }
+}
+
+impl<W: Write> AutoStream<W> {
+    /// Wraps `inner`, resolving `choice` against a caller-supplied terminal
+    /// detection result instead of querying `inner` directly.
+    pub fn with_terminal_hint(inner: W, choice: ColorChoice, is_terminal: bool) -> Self {
+        let repr = match choice {
+            ColorChoice::AlwaysAnsi => Repr::PassThrough(inner),
+            ColorChoice::Always => force_color(inner, is_terminal),
+            ColorChoice::Never => Repr::Strip(StripWriter::new(inner)),
+            ColorChoice::Auto => {
+                if env::var_os("NO_COLOR").is_some() {
+                    Repr::Strip(StripWriter::new(inner))
+                } else if env::var_os("CLICOLOR_FORCE").is_some() {
+                    force_color(inner, is_terminal)
+                } else if !is_terminal || env::var("TERM").is_ok_and(|term| term == "dumb") {
+                    Repr::Strip(StripWriter::new(inner))
+                } else {
+                    force_color(inner, is_terminal)
+                }
+            }
+        };
+
+        Self { repr }
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        match &self.repr {
+            Repr::PassThrough(w) => w,
+            Repr::Strip(w) => w.get_ref(),
+            #[cfg(windows)]
+            Repr::WinApi(w) => w.get_ref(),
+        }
+    }
+
+    /// Returns a mutable reference to the wrapped writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        match &mut self.repr {
+            Repr::PassThrough(w) => w,
+            Repr::Strip(w) => w.get_mut(),
+            #[cfg(windows)]
+            Repr::WinApi(w) => w.get_mut(),
+        }
+    }
+}
+
+impl<W: Write> Write for AutoStream<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.repr {
+            Repr::PassThrough(w) => w.write(buf),
+            Repr::Strip(w) => w.write(buf),
+            #[cfg(windows)]
+            Repr::WinApi(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.repr {
+            Repr::PassThrough(w) => w.flush(),
+            Repr::Strip(w) => w.flush(),
+            #[cfg(windows)]
+            Repr::WinApi(w) => w.flush(),
+        }
+    }
+}
+
+/// Picks the strategy for forcing color, falling back to the winapi replay path on
+/// a legacy Windows console instead of emitting raw ANSI it can't render.
+#[cfg_attr(not(windows), allow(unused_variables))]
+fn force_color<W: Write>(inner: W, is_terminal: bool) -> Repr<W> {
+    #[cfg(windows)]
+    {
+        if is_terminal && !console_supports_ansi() {
+            Repr::WinApi(WinconWriter::new(inner))
+        } else {
+            Repr::PassThrough(inner)
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        Repr::PassThrough(inner)
+    }
+}
+
+/// Returns whether the current Windows console is the modern, ANSI-capable kind
+/// rather than a legacy console.
+#[cfg(windows)]
+fn console_supports_ansi() -> bool {
+    crate::ansi_support::supports_ansi()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // Serializes tests that touch process-wide environment variables.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_env<T>(vars: &[(&str, Option<&str>)], test: impl FnOnce() -> T) -> T {
+        let _guard = ENV_LOCK.lock().unwrap();
+        for (key, _) in vars {
+            env::remove_var(key);
+        }
+        for (key, value) in vars {
+            if let Some(value) = value {
+                env::set_var(key, value);
+            }
+        }
+        let result = test();
+        for (key, _) in vars {
+            env::remove_var(key);
+        }
+        result
+    }
+
+    fn repr_kind<W: Write>(stream: &AutoStream<W>) -> &'static str {
+        match &stream.repr {
+            Repr::PassThrough(_) => "pass_through",
+            Repr::Strip(_) => "strip",
+            #[cfg(windows)]
+            Repr::WinApi(_) => "winapi",
+        }
+    }
+
+    #[test]
+    fn test_always_ansi_passes_through_regardless_of_terminal() {
+        let stream = AutoStream::with_terminal_hint(Vec::new(), ColorChoice::AlwaysAnsi, false);
+        assert_eq!(repr_kind(&stream), "pass_through");
+    }
+
+    #[test]
+    fn test_never_strips_even_on_a_terminal() {
+        let stream = AutoStream::with_terminal_hint(Vec::new(), ColorChoice::Never, true);
+        assert_eq!(repr_kind(&stream), "strip");
+    }
+
+    #[test]
+    fn test_auto_strips_when_not_a_terminal() {
+        with_env(&[("NO_COLOR", None), ("CLICOLOR_FORCE", None)], || {
+            let stream = AutoStream::with_terminal_hint(Vec::new(), ColorChoice::Auto, false);
+            assert_eq!(repr_kind(&stream), "strip");
+        });
+    }
+
+    #[test]
+    fn test_auto_strips_on_no_color() {
+        with_env(&[("NO_COLOR", Some("1")), ("CLICOLOR_FORCE", None)], || {
+            let stream = AutoStream::with_terminal_hint(Vec::new(), ColorChoice::Auto, true);
+            assert_eq!(repr_kind(&stream), "strip");
+        });
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_auto_passes_through_on_a_real_terminal() {
+        with_env(&[("NO_COLOR", None), ("CLICOLOR_FORCE", None)], || {
+            let stream = AutoStream::with_terminal_hint(Vec::new(), ColorChoice::Auto, true);
+            assert_eq!(repr_kind(&stream), "pass_through");
+        });
+    }
+
+    // Regression test: forcing color via `CLICOLOR_FORCE` must go through the same
+    // windows-detection path as `ColorChoice::Always`, not bypass it.
+    #[cfg(not(windows))]
+    #[test]
+    fn test_auto_clicolor_force_matches_always() {
+        with_env(&[("NO_COLOR", None), ("CLICOLOR_FORCE", Some("1"))], || {
+            let forced = AutoStream::with_terminal_hint(Vec::new(), ColorChoice::Auto, false);
+            let always = AutoStream::with_terminal_hint(Vec::new(), ColorChoice::Always, false);
+            assert_eq!(repr_kind(&forced), repr_kind(&always));
+        });
+    }
+}
+
@@ -0,0 +1,84 @@
+use std::io::{self, Write};
+use std::str::Utf8Error;
+
+/// An in-memory sink that accumulates queued command output instead of writing it
+/// straight to a device.
+#[derive(Debug, Default, Clone)]
+pub struct CommandBuffer {
+    buffer: Vec<u8>,
+}
+
+impl CommandBuffer {
+    /// Creates an empty `CommandBuffer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the currently buffered bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Returns the currently buffered bytes interpreted as UTF-8.
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(&self.buffer)
+    }
+
+    /// Discards all buffered bytes without affecting the buffer's capacity.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Writes the buffered bytes to `writer` in a single call, then clears the
+    /// buffer.
+    pub fn drain_to<W: Write>(&mut self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl Write for CommandBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_buffer_accumulates_writes() {
+        let mut buffer = CommandBuffer::new();
+        buffer.write_all(b"\x1B[31m").unwrap();
+        buffer.write_all(b"red").unwrap();
+        assert_eq!(buffer.as_bytes(), b"\x1B[31mred");
+        assert_eq!(buffer.as_str().unwrap(), "\x1B[31mred");
+    }
+
+    #[test]
+    fn test_command_buffer_clear() {
+        let mut buffer = CommandBuffer::new();
+        buffer.write_all(b"queued").unwrap();
+        buffer.clear();
+        assert!(buffer.as_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_command_buffer_drain_to() {
+        let mut buffer = CommandBuffer::new();
+        buffer.write_all(b"frame").unwrap();
+
+        let mut sink = Vec::new();
+        buffer.drain_to(&mut sink).unwrap();
+
+        assert_eq!(sink, b"frame");
+        assert!(buffer.as_bytes().is_empty());
+    }
+}
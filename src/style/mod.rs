@@ -0,0 +1,13 @@
+mod attributes;
+mod auto_stream;
+mod command_buffer;
+mod strip;
+#[cfg(windows)]
+mod wincon;
+
+pub use attributes::{Attributes, SetAttributes};
+pub use auto_stream::{AutoStream, ColorChoice};
+pub use command_buffer::CommandBuffer;
+pub use strip::{strip_ansi, strip_ansi_bytes, StripWriter};
+#[cfg(windows)]
+pub use wincon::WinconWriter;
@@ -0,0 +1,200 @@
+use std::io::{self, Write};
+
+/// Parser state for the incremental ANSI-stripping state machine.
+///
+/// The state is the only thing that needs to survive across `write()` calls: since
+/// escape sequences are discarded entirely, there is nothing to replay once a
+/// sequence completes, only where we are inside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+/// Strips ANSI escape sequences out of `input`, returning the remaining printable text.
+pub fn strip_ansi(input: &str) -> String {
+    // A single-character escape swallows exactly one byte after `ESC`, which can be
+    // the first byte of a multi-byte character; the continuation bytes it leaves
+    // behind are replaced with U+FFFD instead of causing a panic.
+    String::from_utf8_lossy(&strip_ansi_bytes(input.as_bytes())).into_owned()
+}
+
+/// Strips ANSI escape sequences out of `input`, returning the remaining printable bytes.
+pub fn strip_ansi_bytes(input: &[u8]) -> Vec<u8> {
+    let mut writer = StripWriter::new(Vec::with_capacity(input.len()));
+    writer
+        .write_all(input)
+        .expect("writing to a `Vec<u8>` cannot fail");
+    writer.into_inner()
+}
+
+/// A writer adapter that removes ANSI escape sequences before forwarding the
+/// remaining printable bytes to the wrapped writer `W`.
+///
+/// This lets a command pipeline built with [`queue!`](crate::queue) or
+/// [`execute!`](crate::execute) run unchanged against a sink that isn't a real
+/// terminal, such as a file, a pipe, or a `NO_COLOR` environment: wrap the sink in a
+/// `StripWriter` and the styling commands simply disappear instead of leaking raw
+/// escape codes into the output.
+///
+/// The parser is an incremental byte state machine, so an escape sequence split
+/// across two `write()` calls is still recognized and removed correctly.
+#[derive(Debug)]
+pub struct StripWriter<W: Write> {
+    inner: W,
+    state: State,
+}
+
+impl<W: Write> StripWriter<W> {
+    /// Creates a new `StripWriter` wrapping `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            state: State::Ground,
+        }
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the adapter, returning the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for StripWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Printable bytes are written to `inner` in the largest contiguous runs
+        // possible rather than one byte at a time; `run_start` tracks where the
+        // current run began, if any.
+        let mut run_start = None;
+
+        for (i, &byte) in buf.iter().enumerate() {
+            match self.state {
+                State::Ground => {
+                    if byte == 0x1B {
+                        if let Some(start) = run_start.take() {
+                            self.inner.write_all(&buf[start..i])?;
+                        }
+                        self.state = State::Escape;
+                    } else if run_start.is_none() {
+                        run_start = Some(i);
+                    }
+                }
+                State::Escape => {
+                    self.state = match byte {
+                        b'[' => State::Csi,
+                        b']' => State::Osc,
+                        // Any other byte is a single-character escape: it ends the
+                        // sequence immediately and is itself swallowed.
+                        _ => {
+                            run_start = Some(i + 1);
+                            State::Ground
+                        }
+                    };
+                }
+                State::Csi => {
+                    // Parameter bytes (0x30-0x3F) and intermediate bytes (0x20-0x2F)
+                    // are consumed silently while waiting for the final byte.
+                    if (0x40..=0x7E).contains(&byte) {
+                        run_start = Some(i + 1);
+                        self.state = State::Ground;
+                    }
+                }
+                State::Osc => {
+                    if byte == 0x07 {
+                        run_start = Some(i + 1);
+                        self.state = State::Ground;
+                    } else if byte == 0x1B {
+                        self.state = State::OscEscape;
+                    }
+                }
+                State::OscEscape => {
+                    if byte == b'\\' {
+                        run_start = Some(i + 1);
+                        self.state = State::Ground;
+                    } else {
+                        // Not a valid ST, the OSC sequence is still open.
+                        self.state = State::Osc;
+                    }
+                }
+            }
+        }
+
+        if let Some(start) = run_start {
+            self.inner.write_all(&buf[start..])?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Nothing but `state` is buffered across calls, and `state` alone can never
+        // be "half-parsed" bytes waiting to be emitted, so there is nothing to lose
+        // by flushing the inner writer here.
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(input: &[u8]) -> Vec<u8> {
+        strip_ansi_bytes(input)
+    }
+
+    #[test]
+    fn test_strip_plain_text() {
+        assert_eq!(strip(b"hello world"), b"hello world");
+    }
+
+    #[test]
+    fn test_strip_sgr_sequence() {
+        assert_eq!(strip(b"\x1B[31mred\x1B[0m"), b"red");
+    }
+
+    #[test]
+    fn test_strip_cursor_sequence() {
+        assert_eq!(strip(b"\x1B[2J\x1B[1;1Hhi"), b"hi");
+    }
+
+    #[test]
+    fn test_strip_osc_with_bel() {
+        assert_eq!(strip(b"\x1B]0;title\x07visible"), b"visible");
+    }
+
+    #[test]
+    fn test_strip_osc_with_st() {
+        assert_eq!(strip(b"\x1B]0;title\x1B\\visible"), b"visible");
+    }
+
+    #[test]
+    fn test_strip_single_char_escape() {
+        assert_eq!(strip(b"a\x1BMb"), b"ab");
+    }
+
+    #[test]
+    fn test_strip_sequence_split_across_writes() {
+        let mut writer = StripWriter::new(Vec::new());
+        writer.write_all(b"before\x1B[3").unwrap();
+        writer.write_all(b"1mred\x1B[0mafter").unwrap();
+        assert_eq!(writer.into_inner(), b"beforeredafter");
+    }
+
+    #[test]
+    fn test_strip_str_helper() {
+        assert_eq!(strip_ansi("\x1B[32mgreen\x1B[0m text"), "green text");
+    }
+}
@@ -0,0 +1,420 @@
+//! A generic ANSI-to-winapi replay adapter for legacy (pre-Windows 10) consoles.
+
+use std::io::{self, Write};
+
+use windows_sys::Win32::System::Console::{
+    GetConsoleScreenBufferInfo, GetStdHandle, SetConsoleTextAttribute, CONSOLE_SCREEN_BUFFER_INFO,
+    STD_OUTPUT_HANDLE,
+};
+
+const FOREGROUND_RED: u16 = windows_sys::Win32::System::Console::FOREGROUND_RED;
+const FOREGROUND_GREEN: u16 = windows_sys::Win32::System::Console::FOREGROUND_GREEN;
+const FOREGROUND_BLUE: u16 = windows_sys::Win32::System::Console::FOREGROUND_BLUE;
+const FOREGROUND_INTENSITY: u16 = windows_sys::Win32::System::Console::FOREGROUND_INTENSITY;
+const BACKGROUND_RED: u16 = windows_sys::Win32::System::Console::BACKGROUND_RED;
+const BACKGROUND_GREEN: u16 = windows_sys::Win32::System::Console::BACKGROUND_GREEN;
+const BACKGROUND_BLUE: u16 = windows_sys::Win32::System::Console::BACKGROUND_BLUE;
+const BACKGROUND_INTENSITY: u16 = windows_sys::Win32::System::Console::BACKGROUND_INTENSITY;
+
+const FOREGROUND_MASK: u16 =
+    FOREGROUND_RED | FOREGROUND_GREEN | FOREGROUND_BLUE | FOREGROUND_INTENSITY;
+const BACKGROUND_MASK: u16 =
+    BACKGROUND_RED | BACKGROUND_GREEN | BACKGROUND_BLUE | BACKGROUND_INTENSITY;
+
+/// The base 8-color bit patterns for SGR 30-37/40-47, indexed by `code % 10`.
+const BASE_COLOR_BITS: [u16; 8] = [
+    0,                                                     // black
+    FOREGROUND_RED,                                        // red
+    FOREGROUND_GREEN,                                       // green
+    FOREGROUND_RED | FOREGROUND_GREEN,                      // yellow
+    FOREGROUND_BLUE,                                        // blue
+    FOREGROUND_RED | FOREGROUND_BLUE,                       // magenta
+    FOREGROUND_GREEN | FOREGROUND_BLUE,                     // cyan
+    FOREGROUND_RED | FOREGROUND_GREEN | FOREGROUND_BLUE,    // white
+];
+
+/// The console attribute word a sequence of SGR codes would produce.
+///
+/// Shared by [`WinconWriter`] and by
+/// [`SetAttributes`](super::attributes::SetAttributes)'s winapi path.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConsoleAttributeState {
+    default_attributes: u16,
+    current_attributes: u16,
+    reversed: bool,
+}
+
+impl ConsoleAttributeState {
+    /// Creates a state seeded with the console's current attributes, so an SGR
+    /// reset (code `0`) restores them rather than some hardcoded default.
+    pub(crate) fn new() -> Self {
+        let default_attributes = query_current_attributes().unwrap_or(0);
+        Self {
+            default_attributes,
+            current_attributes: default_attributes,
+            reversed: false,
+        }
+    }
+
+    /// Applies one SGR parameter, as would appear between `;` separators in a
+    /// `\x1B[...m` sequence.
+    pub(crate) fn apply_sgr_code(&mut self, code: u32) {
+        match code {
+            0 => {
+                self.current_attributes = self.default_attributes;
+                self.reversed = false;
+            }
+            1 => self.current_attributes |= FOREGROUND_INTENSITY,
+            7 => self.reversed = true,
+            30..=37 => {
+                self.current_attributes =
+                    (self.current_attributes & !FOREGROUND_MASK) | BASE_COLOR_BITS[(code - 30) as usize];
+            }
+            90..=97 => {
+                self.current_attributes = (self.current_attributes & !FOREGROUND_MASK)
+                    | BASE_COLOR_BITS[(code - 90) as usize]
+                    | FOREGROUND_INTENSITY;
+            }
+            40..=47 => {
+                self.current_attributes = (self.current_attributes & !BACKGROUND_MASK)
+                    | (BASE_COLOR_BITS[(code - 40) as usize] << 4);
+            }
+            100..=107 => {
+                self.current_attributes = (self.current_attributes & !BACKGROUND_MASK)
+                    | (BASE_COLOR_BITS[(code - 100) as usize] << 4)
+                    | BACKGROUND_INTENSITY;
+            }
+            // Unrecognized SGR parameters are swallowed rather than applied.
+            _ => {}
+        }
+    }
+
+    /// Composes the tracked attributes into a `SetConsoleTextAttribute` word,
+    /// swapping foreground/background if reverse video is set.
+    pub(crate) fn attribute_word(&self) -> u16 {
+        if self.reversed {
+            ((self.current_attributes & FOREGROUND_MASK) << 4 & BACKGROUND_MASK)
+                | ((self.current_attributes & BACKGROUND_MASK) >> 4 & FOREGROUND_MASK)
+        } else {
+            self.current_attributes
+        }
+    }
+
+    /// Applies a full `;`-separated list of SGR parameters, as parsed out of a
+    /// `\x1B[...m` sequence.
+    ///
+    /// The `38`/`48` extended-color introducers (truecolor `2;r;g;b` and palette
+    /// `5;n`) carry a fixed-arity sub-sequence that has to be consumed as a single
+    /// unit: feeding its components back through [`apply_sgr_code`](Self::apply_sgr_code)
+    /// one at a time would corrupt the console state, since an RGB channel or
+    /// palette index of `0`, `1` or `7` would be misread as reset/bold/reverse.
+    /// Legacy consoles have no true/256-color support to map these onto, so the
+    /// whole sub-sequence is swallowed instead.
+    pub(crate) fn apply_sgr_params(&mut self, params: &[u32]) {
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                38 | 48 => i += 1 + extended_color_len(&params[i + 1..]),
+                code => {
+                    self.apply_sgr_code(code);
+                    i += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Returns how many parameters after a `38`/`48` introducer belong to its
+/// extended-color sub-sequence: 4 for truecolor (`2;r;g;b`), 2 for palette (`5;n`),
+/// or 0 if the sub-format isn't recognized.
+fn extended_color_len(rest: &[u32]) -> usize {
+    match rest.first() {
+        Some(2) => 4.min(rest.len()),
+        Some(5) => 2.min(rest.len()),
+        _ => 0,
+    }
+}
+
+/// Byte-level parser state, mirroring [`strip::StripWriter`](super::strip::StripWriter)
+/// but collecting CSI parameter bytes instead of discarding them, since they're
+/// needed to resolve the SGR codes they carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+/// A writer adapter that parses an incoming ANSI byte stream and translates the SGR
+/// sequences it recognizes into `SetConsoleTextAttribute` calls, writing the plain
+/// text between sequences directly to the wrapped writer `W`.
+///
+/// Like [`StripWriter`](super::strip::StripWriter), it keeps parser state across
+/// `write()` boundaries. Attributes are applied lazily, right before the next run of
+/// printable text, so a run of SGR codes with no text between them costs one
+/// `SetConsoleTextAttribute` call rather than one per code.
+#[derive(Debug)]
+pub struct WinconWriter<W: Write> {
+    inner: W,
+    state: State,
+    params: Vec<u8>,
+    attributes: ConsoleAttributeState,
+    dirty: bool,
+}
+
+impl<W: Write> WinconWriter<W> {
+    /// Creates a new `WinconWriter` wrapping `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            state: State::Ground,
+            params: Vec::new(),
+            attributes: ConsoleAttributeState::new(),
+            dirty: false,
+        }
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the adapter, returning the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn apply_sgr(&mut self, params: &[u32]) {
+        self.attributes.apply_sgr_params(params);
+        self.dirty = true;
+    }
+
+    fn flush_attributes(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.dirty = false;
+        set_console_attributes(self.attributes.attribute_word());
+        Ok(())
+    }
+
+    fn end_csi(&mut self, final_byte: u8) {
+        if final_byte == b'm' {
+            let params: Vec<u32> = self
+                .params
+                .split(|&b| b == b';')
+                .map(|chunk| {
+                    std::str::from_utf8(chunk)
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(0)
+                })
+                .collect();
+            self.apply_sgr(&params);
+        }
+        // Sequences other than SGR (cursor movement, erase, ...) are recognized as
+        // complete but otherwise swallowed: this adapter only replays attributes.
+        self.params.clear();
+        self.state = State::Ground;
+    }
+}
+
+impl<W: Write> Write for WinconWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut run_start = None;
+
+        for (i, &byte) in buf.iter().enumerate() {
+            match self.state {
+                State::Ground => {
+                    if byte == 0x1B {
+                        if let Some(start) = run_start.take() {
+                            if start < i {
+                                self.flush_attributes()?;
+                                self.inner.write_all(&buf[start..i])?;
+                            }
+                        }
+                        self.state = State::Escape;
+                    } else if run_start.is_none() {
+                        run_start = Some(i);
+                    }
+                }
+                State::Escape => {
+                    self.state = match byte {
+                        b'[' => {
+                            self.params.clear();
+                            State::Csi
+                        }
+                        b']' => State::Osc,
+                        _ => {
+                            run_start = Some(i + 1);
+                            State::Ground
+                        }
+                    };
+                }
+                State::Csi => {
+                    if (0x40..=0x7E).contains(&byte) {
+                        self.end_csi(byte);
+                        run_start = Some(i + 1);
+                    } else {
+                        self.params.push(byte);
+                    }
+                }
+                State::Osc => {
+                    if byte == 0x07 {
+                        run_start = Some(i + 1);
+                        self.state = State::Ground;
+                    } else if byte == 0x1B {
+                        self.state = State::OscEscape;
+                    }
+                }
+                State::OscEscape => {
+                    if byte == b'\\' {
+                        run_start = Some(i + 1);
+                        self.state = State::Ground;
+                    } else {
+                        self.state = State::Osc;
+                    }
+                }
+            }
+        }
+
+        if let Some(start) = run_start {
+            if start < buf.len() {
+                self.flush_attributes()?;
+                self.inner.write_all(&buf[start..])?;
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub(crate) fn query_current_attributes() -> Option<u16> {
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = std::mem::zeroed();
+        if GetConsoleScreenBufferInfo(handle, &mut info) == 0 {
+            None
+        } else {
+            Some(info.wAttributes)
+        }
+    }
+}
+
+pub(crate) fn set_console_attributes(attributes: u16) {
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        SetConsoleTextAttribute(handle, attributes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_from(default_attributes: u16) -> ConsoleAttributeState {
+        ConsoleAttributeState {
+            default_attributes,
+            current_attributes: default_attributes,
+            reversed: false,
+        }
+    }
+
+    #[test]
+    fn test_foreground_color() {
+        let mut state = state_from(0);
+        state.apply_sgr_code(31);
+        assert_eq!(state.attribute_word(), FOREGROUND_RED);
+    }
+
+    #[test]
+    fn test_bright_background_color() {
+        let mut state = state_from(0);
+        state.apply_sgr_code(104);
+        assert_eq!(
+            state.attribute_word(),
+            (FOREGROUND_BLUE << 4) | BACKGROUND_INTENSITY
+        );
+    }
+
+    #[test]
+    fn test_intensity_is_additive_with_color() {
+        let mut state = state_from(0);
+        state.apply_sgr_code(31);
+        state.apply_sgr_code(1);
+        assert_eq!(state.attribute_word(), FOREGROUND_RED | FOREGROUND_INTENSITY);
+    }
+
+    #[test]
+    fn test_reverse_swaps_foreground_and_background() {
+        let mut state = state_from(0);
+        state.apply_sgr_code(31); // red foreground
+        state.apply_sgr_code(44); // blue background
+        state.apply_sgr_code(7); // reverse
+        assert_eq!(state.attribute_word(), (FOREGROUND_RED << 4) | FOREGROUND_BLUE);
+    }
+
+    #[test]
+    fn test_reset_restores_default_attributes() {
+        let mut state = state_from(FOREGROUND_GREEN);
+        state.apply_sgr_code(31);
+        state.apply_sgr_code(7);
+        state.apply_sgr_code(0);
+        assert_eq!(state.attribute_word(), FOREGROUND_GREEN);
+    }
+
+    #[test]
+    fn test_unrecognized_code_is_ignored() {
+        let mut state = state_from(0);
+        state.apply_sgr_code(31);
+        state.apply_sgr_code(38); // unsupported extended-color introducer
+        assert_eq!(state.attribute_word(), FOREGROUND_RED);
+    }
+
+    #[test]
+    fn test_truecolor_foreground_is_swallowed_not_misapplied() {
+        let mut state = state_from(0);
+        state.apply_sgr_code(31); // baseline: red foreground
+        // A naive per-code parser would read the leading `0` of `0;255;0` as an SGR
+        // reset, wiping out the red set above.
+        state.apply_sgr_params(&[38, 2, 0, 255, 0]);
+        assert_eq!(state.attribute_word(), FOREGROUND_RED);
+    }
+
+    #[test]
+    fn test_truecolor_background_is_swallowed_not_misapplied() {
+        let mut state = state_from(0);
+        state.apply_sgr_code(31);
+        state.apply_sgr_params(&[48, 2, 0, 255, 0]);
+        assert_eq!(state.attribute_word(), FOREGROUND_RED);
+    }
+
+    #[test]
+    fn test_palette_color_is_swallowed_not_misapplied() {
+        let mut state = state_from(0);
+        state.apply_sgr_code(31); // baseline: red foreground
+        // A naive per-code parser would read the palette index `1` as SGR bold.
+        state.apply_sgr_params(&[38, 5, 1]);
+        assert_eq!(state.attribute_word(), FOREGROUND_RED);
+    }
+
+    #[test]
+    fn test_extended_color_does_not_swallow_following_codes() {
+        let mut state = state_from(0);
+        state.apply_sgr_params(&[38, 2, 0, 1, 7, 1]);
+        // Only the 4 parameters after the introducer (`2;0;1;7`) belong to the
+        // truecolor sub-sequence; the trailing `1` is a standalone bold code.
+        assert_eq!(state.attribute_word(), FOREGROUND_INTENSITY);
+    }
+}